@@ -21,6 +21,7 @@ use std::error::Error;
 pub struct HttpClientError {
     kind: ErrorKind,
     cause: Option<Box<dyn Error + Send + Sync>>,
+    url: Option<String>,
 }
 
 impl HttpClientError {
@@ -37,6 +38,7 @@ impl HttpClientError {
         Self {
             kind: ErrorKind::UserAborted,
             cause: None,
+            url: None,
         }
     }
 
@@ -55,6 +57,7 @@ impl HttpClientError {
         Self {
             kind: ErrorKind::Other,
             cause: cause.map(|e| e.into()),
+            url: None,
         }
     }
 
@@ -72,6 +75,143 @@ impl HttpClientError {
         self.kind
     }
 
+    /// Gets the URI or host this error is about, if any. This is populated
+    /// only for `Tls` and `Connect` errors raised by a TLS handshake with a
+    /// specific host, and then holds that host; other errors, including
+    /// `Redirect` errors, carry no URI.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ylong_http_client::HttpClientError;
+    ///
+    /// let user_aborted = HttpClientError::user_aborted();
+    /// assert_eq!(user_aborted.url(), None);
+    /// ```
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    /// Returns a reference to the underlying cause of this error, if any, so
+    /// it can be inspected or downcast to a concrete error type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ylong_http_client::HttpClientError;
+    ///
+    /// let user_aborted = HttpClientError::user_aborted();
+    /// assert!(user_aborted.cause().is_none());
+    /// ```
+    pub fn cause(&self) -> Option<&(dyn Error + 'static)> {
+        self.cause.as_ref().map(|c| &**c as &(dyn Error + 'static))
+    }
+
+    /// Consumes this error, returning the boxed underlying cause, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ylong_http_client::HttpClientError;
+    ///
+    /// let user_aborted = HttpClientError::user_aborted();
+    /// assert!(user_aborted.into_cause().is_none());
+    /// ```
+    pub fn into_cause(self) -> Option<Box<dyn Error + Send + Sync>> {
+        self.cause
+    }
+
+    /// Returns `true` if this error was caused by reaching a timeout.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ylong_http_client::HttpClientError;
+    ///
+    /// let user_aborted = HttpClientError::user_aborted();
+    /// assert!(!user_aborted.is_timeout());
+    /// ```
+    pub fn is_timeout(&self) -> bool {
+        matches!(self.kind, ErrorKind::Timeout)
+    }
+
+    /// Returns `true` if this error was caused by a failure to connect to the server.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ylong_http_client::HttpClientError;
+    ///
+    /// let user_aborted = HttpClientError::user_aborted();
+    /// assert!(!user_aborted.is_connect());
+    /// ```
+    pub fn is_connect(&self) -> bool {
+        matches!(self.kind, ErrorKind::Connect)
+    }
+
+    /// Returns `true` if this error was caused while following a redirect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ylong_http_client::HttpClientError;
+    ///
+    /// let user_aborted = HttpClientError::user_aborted();
+    /// assert!(!user_aborted.is_redirect());
+    /// ```
+    pub fn is_redirect(&self) -> bool {
+        matches!(self.kind, ErrorKind::Redirect)
+    }
+
+    /// Returns `true` if this error was caused while transferring or decoding a body.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ylong_http_client::HttpClientError;
+    ///
+    /// let user_aborted = HttpClientError::user_aborted();
+    /// assert!(!user_aborted.is_body());
+    /// ```
+    pub fn is_body(&self) -> bool {
+        matches!(self.kind, ErrorKind::BodyDecode | ErrorKind::BodyTransfer)
+    }
+
+    /// Returns `true` if this error was raised by the user aborting the request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ylong_http_client::HttpClientError;
+    ///
+    /// let user_aborted = HttpClientError::user_aborted();
+    /// assert!(user_aborted.is_user_aborted());
+    /// ```
+    pub fn is_user_aborted(&self) -> bool {
+        matches!(self.kind, ErrorKind::UserAborted)
+    }
+
+    /// Returns `true` if this error was caused by a TLS handshake or certificate
+    /// verification failure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ylong_http_client::HttpClientError;
+    ///
+    /// let user_aborted = HttpClientError::user_aborted();
+    /// assert!(!user_aborted.is_tls());
+    /// ```
+    pub fn is_tls(&self) -> bool {
+        matches!(self.kind, ErrorKind::Tls)
+    }
+
+    /// Attaches the URI this error is about, returning the updated error.
+    pub(crate) fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
     pub(crate) fn new_with_cause<T>(kind: ErrorKind, cause: Option<T>) -> Self
     where
         T: Into<Box<dyn Error + Send + Sync>>,
@@ -79,6 +219,7 @@ impl HttpClientError {
         Self {
             kind,
             cause: cause.map(|e| e.into()),
+            url: None,
         }
     }
 
@@ -86,8 +227,19 @@ impl HttpClientError {
         Self {
             kind,
             cause: Some(CauseMessage::new(message).into()),
+            url: None,
         }
     }
+
+    /// Creates a `Tls` error, recording the certificate verify result and the
+    /// SSL handshake state so the failure can be diagnosed without losing the
+    /// `Tls` kind.
+    pub(crate) fn new_with_tls_info(verify_result: &str, ssl_state: &str) -> Self {
+        Self::new_with_message(
+            ErrorKind::Tls,
+            &format!("verify result: {verify_result}, ssl state: {ssl_state}"),
+        )
+    }
 }
 
 impl Debug for HttpClientError {
@@ -97,6 +249,9 @@ impl Debug for HttpClientError {
         if let Some(ref cause) = self.cause {
             builder.field("Cause", cause);
         }
+        if let Some(ref url) = self.url {
+            builder.field("Url", url);
+        }
         builder.finish()
     }
 }
@@ -108,11 +263,18 @@ impl Display for HttpClientError {
         if let Some(ref cause) = self.cause {
             write!(f, ": {cause}")?;
         }
+        if let Some(ref url) = self.url {
+            write!(f, " for url ({url})")?;
+        }
         Ok(())
     }
 }
 
-impl Error for HttpClientError {}
+impl Error for HttpClientError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.cause()
+    }
+}
 
 /// Error kinds which can indicate the type of a `HttpClientError`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -144,6 +306,9 @@ pub enum ErrorKind {
     /// Errors for reaching a timeout.
     Timeout,
 
+    /// Errors for TLS handshake or certificate verification failures.
+    Tls,
+
     /// User raised errors.
     UserAborted,
 }
@@ -169,6 +334,7 @@ impl ErrorKind {
             Self::Redirect => "Redirect Error",
             Self::Request => "Request Error",
             Self::Timeout => "Timeout Error",
+            Self::Tls => "Tls Error",
             Self::UserAborted => "User Aborted Error",
         }
     }
@@ -18,22 +18,29 @@ use crate::{
         ffi::{
             bio::BIO,
             ssl::{
-                SSL_connect, SSL_ctrl, SSL_get0_param, SSL_get_error, SSL_get_rbio,
-                SSL_get_verify_result, SSL_read, SSL_state_string_long, SSL_write,
+                SSL_connect, SSL_ctrl, SSL_get0_alpn_selected, SSL_get0_param, SSL_get_error,
+                SSL_get_rbio, SSL_get_verify_result, SSL_read, SSL_state_string_long, SSL_write,
+                SSL_CTX_check_private_key, SSL_CTX_set_alpn_protos, SSL_CTX_set_verify,
+                SSL_CTX_use_PrivateKey, SSL_CTX_use_certificate, SSL_VERIFY_NONE,
             },
+            x509::{SSL_CTX_get_cert_store, X509_STORE_add_cert},
         },
         foreign::ForeignRef,
-        x509::{X509VerifyParamRef, X509VerifyResult, X509_CHECK_FLAG_NO_PARTIAL_WILDCARDS},
+        pkey::PKeyRef,
+        x509::{
+            X509Ref, X509VerifyParamRef, X509VerifyResult, X509_CHECK_FLAG_NO_PARTIAL_WILDCARDS,
+        },
     },
     util::c_openssl::{
         check_ptr,
         error::ErrorStack,
         ffi::ssl::{SSL_free, SSL_new, SSL},
         foreign::Foreign,
+        x509::X509,
     },
 };
-use core::{cmp, ffi, fmt, str};
-use libc::{c_char, c_int, c_long, c_void};
+use core::{cmp, ffi, fmt, ptr, slice, str};
+use libc::{c_char, c_int, c_long, c_uint, c_void};
 use std::{
     ffi::CString,
     io::{Read, Write},
@@ -82,6 +89,53 @@ impl Ssl {
             }
         }
     }
+
+    /// Client connect to Server, driven by the async runtime instead of
+    /// blocking a thread on `WANT_READ`/`WANT_WRITE`.
+    /// only `async_impl` use.
+    #[cfg(feature = "async")]
+    pub(crate) async fn async_connect<S>(
+        self,
+        stream: S,
+    ) -> Result<SslStream<S>, HandshakeError<S>>
+    where
+        S: AsyncReadyIo,
+    {
+        let mut stream = SslStream::new_base(self, stream)?;
+        loop {
+            let ret = unsafe { SSL_connect(stream.ssl.as_ptr()) };
+            if ret > 0 {
+                return Ok(stream);
+            }
+            let error = stream.get_error(ret);
+            match error.code {
+                SslErrorCode::WANT_READ => {
+                    core::future::poll_fn(|cx| stream.get_ref().poll_read_ready(cx)).await;
+                }
+                SslErrorCode::WANT_WRITE => {
+                    core::future::poll_fn(|cx| stream.get_ref().poll_write_ready(cx)).await;
+                }
+                _ => {
+                    return Err(HandshakeError::Failure(MidHandshakeSslStream {
+                        _stream: stream,
+                        error,
+                    }))
+                }
+            }
+        }
+    }
+}
+
+/// Implemented by the async stream backing a `MidHandshakeSslStream`'s BIO, so
+/// the async handshake driver can wait for read/write readiness on it instead
+/// of blocking a runtime thread. The crate's async transport (the
+/// runtime-backed TCP stream wrapper used by `async_impl`) is the expected
+/// implementor; this checkout doesn't include that module, so the trait has
+/// no implementor to instantiate `async_connect` with here.
+#[cfg(feature = "async")]
+pub(crate) trait AsyncReadyIo {
+    fn poll_read_ready(&self, cx: &mut core::task::Context<'_>) -> core::task::Poll<()>;
+    fn poll_write_ready(&self, cx: &mut core::task::Context<'_>) -> core::task::Poll<()>;
 }
 
 impl SslRef {
@@ -130,7 +184,17 @@ impl SslRef {
         unsafe { X509VerifyParamRef::from_ptr_mut(SSL_get0_param(self.as_ptr())) }
     }
 
-    pub(crate) fn setup_verify_hostname(ssl: &mut SslRef, host: &str) -> Result<(), ErrorStack> {
+    /// Configures hostname verification for `host`, unless
+    /// `accept_invalid_hostnames` is set, in which case verification is left
+    /// disabled and the peer's certificate is accepted for any hostname.
+    pub(crate) fn setup_verify_hostname(
+        ssl: &mut SslRef,
+        host: &str,
+        accept_invalid_hostnames: bool,
+    ) -> Result<(), ErrorStack> {
+        if accept_invalid_hostnames {
+            return Ok(());
+        }
         let param = ssl.param_mut();
         param.set_hostflags(X509_CHECK_FLAG_NO_PARTIAL_WILDCARDS);
         match host.parse() {
@@ -138,6 +202,328 @@ impl SslRef {
             Err(_) => param.set_host(host),
         }
     }
+
+    /// Builds a `Tls` `HttpClientError` describing why the handshake failed,
+    /// carrying both the `X509VerifyResult` (e.g. "unable to get local issuer
+    /// certificate") and the current SSL state so callers can tell a
+    /// certificate verification failure apart from a plain connect failure.
+    pub(crate) fn tls_error(&self) -> crate::error::HttpClientError {
+        crate::error::HttpClientError::new_with_tls_info(
+            &self.verify_result().to_string(),
+            self.ssl_status(),
+        )
+    }
+
+    /// Gets the protocol negotiated via ALPN during the handshake, if the peer
+    /// selected one. `None` means the server didn't select a protocol, in
+    /// which case the connection layer should fall back to its default.
+    pub(crate) fn selected_alpn(&self) -> Option<Vec<u8>> {
+        unsafe {
+            let mut data: *const u8 = ptr::null();
+            let mut len: c_uint = 0;
+            SSL_get0_alpn_selected(self.as_ptr(), &mut data, &mut len);
+            if data.is_null() {
+                None
+            } else {
+                Some(slice::from_raw_parts(data, len as usize).to_vec())
+            }
+        }
+    }
+}
+
+impl SslContext {
+    /// Adds an extra trusted root certificate to this context's `X509_STORE`,
+    /// augmenting the system trust store rather than replacing it, so chains
+    /// issued by a private/enterprise CA can still be verified.
+    pub(crate) fn add_trust_anchor(&mut self, cert: &X509Ref) -> Result<(), ErrorStack> {
+        unsafe {
+            let store = SSL_CTX_get_cert_store(self.as_ptr());
+            check_ret(X509_STORE_add_cert(store, cert.as_ptr()))
+        }
+        .map(|_| ())
+    }
+
+    /// Adds an extra trusted root certificate in PEM form.
+    pub(crate) fn add_trust_anchor_pem(&mut self, pem: &[u8]) -> Result<(), ErrorStack> {
+        let cert = X509::from_pem(pem)?;
+        self.add_trust_anchor(&cert)
+    }
+
+    /// Adds an extra trusted root certificate in DER form.
+    pub(crate) fn add_trust_anchor_der(&mut self, der: &[u8]) -> Result<(), ErrorStack> {
+        let cert = X509::from_der(der)?;
+        self.add_trust_anchor(&cert)
+    }
+
+    /// Sets the ordered list of protocols (e.g. `["h2", "http/1.1"]`) to offer
+    /// during ALPN negotiation.
+    pub(crate) fn set_alpn_protos(
+        &mut self,
+        protocols: &[&str],
+    ) -> Result<(), crate::error::HttpClientError> {
+        // Each entry is wire-encoded as a single length byte followed by its
+        // ASCII name, so anything longer than `u8::MAX` can't be represented
+        // and must be rejected rather than silently truncated. This is
+        // caught here rather than by OpenSSL, so report it with its own
+        // message instead of draining whatever happens to already be on the
+        // thread-local error queue.
+        if let Some(protocol) = protocols.iter().find(|p| p.len() > u8::MAX as usize) {
+            return Err(crate::error::HttpClientError::new_with_message(
+                crate::error::ErrorKind::Build,
+                &format!("ALPN protocol name exceeds 255 bytes: {protocol}"),
+            ));
+        }
+        let wire = alpn_wire_format(protocols);
+        // `SSL_CTX_set_alpn_protos` uses the opposite convention from most
+        // OpenSSL calls: it returns 0 on success and non-zero on failure.
+        if unsafe { SSL_CTX_set_alpn_protos(self.as_ptr(), wire.as_ptr(), wire.len() as c_uint) }
+            != 0
+        {
+            return Err(build_error(ErrorStack::get()));
+        }
+        Ok(())
+    }
+
+    /// Sets the certificate to present during the handshake, e.g. for mutual
+    /// TLS.
+    pub(crate) fn set_certificate(&mut self, cert: &X509Ref) -> Result<(), ErrorStack> {
+        check_ret(unsafe { SSL_CTX_use_certificate(self.as_ptr(), cert.as_ptr()) }).map(|_| ())
+    }
+
+    /// Sets the private key matching the certificate set via
+    /// [`set_certificate`](SslContext::set_certificate).
+    pub(crate) fn set_private_key(&mut self, key: &PKeyRef) -> Result<(), ErrorStack> {
+        check_ret(unsafe { SSL_CTX_use_PrivateKey(self.as_ptr(), key.as_ptr()) }).map(|_| ())
+    }
+
+    /// Checks that the configured private key matches the configured
+    /// certificate, so a mismatch is caught at build time instead of failing
+    /// mid-handshake.
+    pub(crate) fn check_private_key(&self) -> Result<(), crate::error::HttpClientError> {
+        check_ret(unsafe { SSL_CTX_check_private_key(self.as_ptr()) })
+            .map(|_| ())
+            .map_err(build_error)
+    }
+
+    /// Sets the client certificate and private key to present for mutual TLS,
+    /// verifying the pair is consistent before returning. A mismatched key
+    /// fails here, at build time, with an `ErrorKind::Build` error rather
+    /// than surfacing mid-handshake.
+    pub(crate) fn set_certificate_and_key(
+        &mut self,
+        cert: &X509Ref,
+        key: &PKeyRef,
+    ) -> Result<(), crate::error::HttpClientError> {
+        self.set_certificate(cert).map_err(build_error)?;
+        self.set_private_key(key).map_err(build_error)?;
+        self.check_private_key()
+    }
+
+    /// Disables certificate verification entirely. Opt-in only, and meant for
+    /// testing against self-signed servers and local dev proxies; the verify
+    /// result is still available afterwards via
+    /// [`SslRef::verify_result`](SslRef::verify_result) so callers can log
+    /// what would otherwise have failed.
+    pub(crate) fn danger_accept_invalid_certs(&mut self) {
+        unsafe { SSL_CTX_set_verify(self.as_ptr(), SSL_VERIFY_NONE, None) }
+    }
+}
+
+/// Incrementally configures an [`SslContext`] before it's used to drive a
+/// handshake. Trust anchors, ALPN protocols and the client certificate all go
+/// through this builder instead of being mutated on the context directly, so
+/// there's a single entry point a connector can build up and hand off.
+pub(crate) struct SslContextBuilder {
+    ctx: SslContext,
+    accept_invalid_hostnames: bool,
+}
+
+impl SslContextBuilder {
+    /// Starts from `ctx` with both danger toggles off: certificate
+    /// verification is whatever `ctx` already has configured, and hostname
+    /// verification runs normally. Only
+    /// [`danger_accept_invalid_certs`](SslContextBuilder::danger_accept_invalid_certs)
+    /// and
+    /// [`danger_accept_invalid_hostnames`](SslContextBuilder::danger_accept_invalid_hostnames)
+    /// turn them off, and only when called explicitly.
+    pub(crate) fn new(ctx: SslContext) -> Self {
+        Self {
+            ctx,
+            accept_invalid_hostnames: false,
+        }
+    }
+
+    /// Adds an extra trusted root certificate. See
+    /// [`SslContext::add_trust_anchor`].
+    pub(crate) fn add_trust_anchor(
+        mut self,
+        cert: &X509Ref,
+    ) -> Result<Self, crate::error::HttpClientError> {
+        self.ctx.add_trust_anchor(cert).map_err(build_error)?;
+        Ok(self)
+    }
+
+    /// Adds an extra trusted root certificate in PEM form. See
+    /// [`SslContext::add_trust_anchor_pem`].
+    pub(crate) fn add_trust_anchor_pem(
+        mut self,
+        pem: &[u8],
+    ) -> Result<Self, crate::error::HttpClientError> {
+        self.ctx.add_trust_anchor_pem(pem).map_err(build_error)?;
+        Ok(self)
+    }
+
+    /// Adds an extra trusted root certificate in DER form. See
+    /// [`SslContext::add_trust_anchor_der`].
+    pub(crate) fn add_trust_anchor_der(
+        mut self,
+        der: &[u8],
+    ) -> Result<Self, crate::error::HttpClientError> {
+        self.ctx.add_trust_anchor_der(der).map_err(build_error)?;
+        Ok(self)
+    }
+
+    /// Sets the ordered list of protocols to offer during ALPN negotiation.
+    /// See [`SslContext::set_alpn_protos`].
+    pub(crate) fn set_alpn_protos(
+        mut self,
+        protocols: &[&str],
+    ) -> Result<Self, crate::error::HttpClientError> {
+        self.ctx.set_alpn_protos(protocols)?;
+        Ok(self)
+    }
+
+    /// Sets the client certificate and private key to present for mutual
+    /// TLS. See [`SslContext::set_certificate_and_key`]. The certificate and
+    /// key configured here take effect on the [`SslConnector`] produced by
+    /// [`build`](SslContextBuilder::build), not on `self.ctx` alone.
+    pub(crate) fn set_certificate_and_key(
+        mut self,
+        cert: &X509Ref,
+        key: &PKeyRef,
+    ) -> Result<Self, crate::error::HttpClientError> {
+        self.ctx.set_certificate_and_key(cert, key)?;
+        Ok(self)
+    }
+
+    /// Disables certificate verification entirely. See
+    /// [`SslContext::danger_accept_invalid_certs`].
+    pub(crate) fn danger_accept_invalid_certs(mut self) -> Self {
+        self.ctx.danger_accept_invalid_certs();
+        self
+    }
+
+    /// Accepts the peer's certificate for any hostname, skipping the
+    /// hostname check normally applied via
+    /// [`SslRef::setup_verify_hostname`](SslRef::setup_verify_hostname). Opt-in
+    /// only, for the same testing/local-dev cases as
+    /// [`danger_accept_invalid_certs`](SslContextBuilder::danger_accept_invalid_certs).
+    pub(crate) fn danger_accept_invalid_hostnames(mut self) -> Self {
+        self.accept_invalid_hostnames = true;
+        self
+    }
+
+    /// Finishes configuration, producing the [`SslConnector`] that drives a
+    /// handshake with these settings. This is the only way to obtain an
+    /// `SslConnector`, so every builder method above has a real path to an
+    /// actual handshake attempt instead of configuring a context nothing
+    /// ever connects with.
+    pub(crate) fn build(self) -> SslConnector {
+        SslConnector::new(self.ctx, self.accept_invalid_hostnames)
+    }
+}
+
+impl<S> MidHandshakeSslStream<S> {
+    /// Builds the `Tls` `HttpClientError` for this failed handshake, so a
+    /// certificate or hostname verification failure is distinguishable from
+    /// a generic connect failure instead of collapsing into
+    /// `ErrorKind::Connect`.
+    pub(crate) fn tls_error(&self) -> crate::error::HttpClientError {
+        self._stream.ssl.tls_error()
+    }
+}
+
+/// Drives a TLS handshake to `host` over a stream, translating a failed
+/// handshake into an `HttpClientError` (via
+/// [`MidHandshakeSslStream::tls_error`]) instead of leaving callers to unpack
+/// `HandshakeError` themselves.
+pub(crate) struct SslConnector {
+    ctx: SslContext,
+    accept_invalid_hostnames: bool,
+}
+
+impl SslConnector {
+    fn new(ctx: SslContext, accept_invalid_hostnames: bool) -> Self {
+        Self {
+            ctx,
+            accept_invalid_hostnames,
+        }
+    }
+
+    #[cfg(feature = "sync")]
+    pub(crate) fn connect<S>(
+        &self,
+        host: &str,
+        stream: S,
+    ) -> Result<SslStream<S>, crate::error::HttpClientError>
+    where
+        S: Read + Write,
+    {
+        let mut ssl = Ssl::new(&self.ctx).map_err(build_error)?;
+        SslRef::setup_verify_hostname(&mut ssl, host, self.accept_invalid_hostnames)
+            .map_err(build_error)?;
+        match ssl.connect(stream) {
+            Ok(stream) => Ok(stream),
+            Err(HandshakeError::Failure(mid)) => Err(mid.tls_error().with_url(host)),
+            Err(HandshakeError::WouldBlock(_)) => Err(
+                crate::error::HttpClientError::new_with_message(
+                    crate::error::ErrorKind::Connect,
+                    "handshake did not complete on a blocking stream",
+                )
+                .with_url(host),
+            ),
+        }
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) async fn async_connect<S>(
+        &self,
+        host: &str,
+        stream: S,
+    ) -> Result<SslStream<S>, crate::error::HttpClientError>
+    where
+        S: AsyncReadyIo,
+    {
+        let mut ssl = Ssl::new(&self.ctx).map_err(build_error)?;
+        SslRef::setup_verify_hostname(&mut ssl, host, self.accept_invalid_hostnames)
+            .map_err(build_error)?;
+        match ssl.async_connect(stream).await {
+            Ok(stream) => Ok(stream),
+            Err(HandshakeError::Failure(mid)) => Err(mid.tls_error().with_url(host)),
+            Err(HandshakeError::WouldBlock(_)) => unreachable!(
+                "async_connect polls to completion instead of ever returning WouldBlock"
+            ),
+        }
+    }
+}
+
+/// Converts a raw OpenSSL `ErrorStack` into a `Build`-kind `HttpClientError`,
+/// for the configuration calls above that fail while a context or handshake
+/// is still being put together rather than while it's in use.
+fn build_error(stack: ErrorStack) -> crate::error::HttpClientError {
+    crate::error::HttpClientError::new_with_cause(crate::error::ErrorKind::Build, Some(stack))
+}
+
+/// Encodes an ordered protocol list as the length-prefixed wire format
+/// OpenSSL expects for ALPN, e.g. `["h2", "http/1.1"]` becomes
+/// `0x02 h2 0x08 http/1.1`.
+fn alpn_wire_format(protocols: &[&str]) -> Vec<u8> {
+    let mut wire = Vec::new();
+    for protocol in protocols {
+        wire.push(protocol.len() as u8);
+        wire.extend_from_slice(protocol.as_bytes());
+    }
+    wire
 }
 
 impl fmt::Debug for SslRef {